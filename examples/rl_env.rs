@@ -0,0 +1,64 @@
+//! drives the headless `MulticopterEnv` for a few random steps, the way a training loop would
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_quadcopter::{
+    hover_reward, FrameConfig, MotorModel, Multicopter, MulticopterEnv, QuadcopterControls,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const THRUST_CONSTANT: f32 = 1e-6;
+const DRAG_CONSTANT: f32 = 1e-7;
+const ARM_RADIUS: f32 = 0.0707;
+
+fn spawn_quadcopter(world: &mut World, seed: Option<u64>) -> Entity {
+    let frame = FrameConfig::quad_x();
+    let propellers = frame.propellers(ARM_RADIUS, THRUST_CONSTANT, DRAG_CONSTANT);
+    let quad_mass = 0.4;
+    let g = world.resource::<Gravity>().0.y.abs();
+    let hover_thrust_per_prop = quad_mass * g / frame.motors.len() as f32;
+    let hover_omega = (hover_thrust_per_prop / THRUST_CONSTANT).sqrt();
+
+    // randomize the horizontal spawn offset, seeded so `reset(Some(seed))` is reproducible
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let spawn_offset = Vec3::new(rng.gen_range(-0.5..0.5), 0., rng.gen_range(-0.5..0.5));
+
+    world
+        .spawn((
+            Transform::from_translation(Vec3::new(0., 2., 0.) + spawn_offset),
+            RigidBody::Dynamic,
+            Collider::cuboid(0.1, 0.04, 0.1),
+            QuadcopterControls(vec![0.; frame.motors.len()]),
+            Multicopter::new(propellers).unwrap(),
+            MotorModel::new(0.02, hover_omega * 3., frame.motors.len()),
+            frame,
+            Mass(quad_mass),
+            AngularInertia::new(Vec3::splat(1e-2)),
+        ))
+        .id()
+}
+
+fn main() {
+    let target = Vec3::new(0., 2., 0.);
+    let mut env = MulticopterEnv::new(
+        1. / 60.,
+        300,
+        Box::new(spawn_quadcopter),
+        hover_reward(target),
+    );
+
+    let mut observation = env.reset(Some(0));
+    for _ in 0..300 {
+        // a real training loop would query a policy here; this just holds a rough hover command
+        let action = vec![990.0_f32; 4];
+        let (next_observation, reward, terminated, truncated) = env.step(action);
+        observation = next_observation;
+        println!("reward: {reward}, pos: {:?}", observation.position);
+        if terminated || truncated {
+            observation = env.reset(Some(0));
+        }
+    }
+    let _ = observation;
+}
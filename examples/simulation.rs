@@ -1,8 +1,13 @@
+use std::collections::VecDeque;
+
 use avian3d::prelude::*;
 use bevy::{core_pipeline::Skybox, prelude::*};
 use bevy_egui::EguiPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use bevy_quadcopter::{Multicopter, PropellerInfo, RotationDirection};
+use bevy_quadcopter::{
+    angle_diff, fade_rate, quadcopter_dynamics, AeroConfig, BatteryModel, FrameConfig, MotorModel,
+    Multicopter, QuadcopterControls, SimulatedImu, Wind,
+};
 
 fn main() {
     App::new()
@@ -12,6 +17,10 @@ fn main() {
             brightness: 1.0 / 5.0f32,
             ..default()
         })
+        // mild voltage sag under heavy current draw
+        .insert_resource(BatteryModel::new(12.6, 5e-9))
+        // a gentle steady breeze with a slow gust, to exercise disturbance rejection
+        .insert_resource(Wind::new(Vec3::new(0.5, 0., 0.), Vec3::new(0.3, 0., 0.3), 0.5))
         .add_plugins((
             DefaultPlugins,
             PhysicsPlugins::default(),
@@ -20,8 +29,21 @@ fn main() {
             PhysicsDebugPlugin::default(),
         ))
         .add_systems(Startup, setup)
-        .add_systems(Update, (control_quadcopter, change_window_title))
-        .add_systems(FixedUpdate, (quadcopter_dynamics, camera_follow_quadcopter))
+        .add_systems(
+            Update,
+            (
+                (advance_waypoint_queue, control_quadcopter).chain(),
+                change_window_title,
+            ),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                quadcopter_dynamics,
+                update_simulated_imus,
+                camera_follow_quadcopter,
+            ),
+        )
         .run();
 }
 
@@ -32,17 +54,7 @@ fn change_window_title(mut window: Single<&mut Window>) {
 // this value gives ~10k RPM at hover, which seems reasonable
 const THRUST_CONSTANT: f32 = 1e-6;
 const DRAG_CONSTANT: f32 = 1e-7;
-
-// helper for this quad's propellers
-fn propeller_from_position(x: f32, y: f32, z: f32, direction: RotationDirection) -> PropellerInfo {
-    PropellerInfo {
-        position: Vec3 { x, y, z },
-        direction: Dir3::new(Vec3::Y).unwrap(),
-        thrust_constant: THRUST_CONSTANT,
-        drag_constant: DRAG_CONSTANT,
-        rotation_direction: direction,
-    }
-}
+const ARM_RADIUS: f32 = 0.0707; // ~0.05 m along each axis, as the old hardcoded quad used
 
 pub fn setup(
     mut commands: Commands,
@@ -82,17 +94,13 @@ pub fn setup(
         .looking_at(Vec3::ZERO, Vec3::Y),
     ));
 
-    let propellers = vec![
-        propeller_from_position(0.05, 0., 0.05, RotationDirection::CounterClockWise),
-        propeller_from_position(-0.05, 0., 0.05, RotationDirection::ClockWise),
-        propeller_from_position(0.05, 0., -0.05, RotationDirection::ClockWise),
-        propeller_from_position(-0.05, 0., -0.05, RotationDirection::CounterClockWise),
-    ];
+    let frame = FrameConfig::quad_x();
+    let propellers = frame.propellers(ARM_RADIUS, THRUST_CONSTANT, DRAG_CONSTANT);
 
     let quad_mass = 0.4; // kg
     let g = gravity.0.y.abs();
-    // counteract the force of gravity, m * g
-    let hover_thrust_per_prop = quad_mass * g / 4.;
+    // counteract the force of gravity, spread evenly across however many motors we have
+    let hover_thrust_per_prop = quad_mass * g / frame.motors.len() as f32;
 
     // thrust = k * omega^2
     let hover_omega = (hover_thrust_per_prop / THRUST_CONSTANT).sqrt();
@@ -104,8 +112,13 @@ pub fn setup(
         Transform::from_xyz(0., 2., 0.),
         // it's fine for the fine collision details of the quad to be lost
         Collider::cuboid(0.1, 0.04, 0.1),
-        QuadcopterControls([hover_omega; 4]),
-        Multicopter::new(propellers).unwrap(),
+        QuadcopterControls(vec![hover_omega; frame.motors.len()]),
+        Multicopter::new(propellers)
+            .unwrap()
+            .with_aero(AeroConfig::new(1.225, 1.0, 0.02, 0.05)),
+        SimulatedImu::default(),
+        MotorModel::new(0.02, hover_omega * 3., frame.motors.len()),
+        frame,
         Mass(quad_mass),
         AngularInertia::new(Vec3::splat(1e-2)),
         SweptCcd::NON_LINEAR,
@@ -122,27 +135,56 @@ pub fn setup(
     ));
 }
 
-#[derive(Component, Reflect)]
+/// the quadcopter's current target world position
+///
+/// when present, the position/velocity cascade in `control_quadcopter` takes over from the
+/// manual altitude-hold + stick controls
+#[derive(Component, Reflect, Debug, Clone, Copy)]
 #[reflect(Component)]
-pub struct QuadcopterControls([f32; 4]);
+pub struct Waypoint(pub Vec3);
+
+/// a queue of waypoints to fly to in order, in addition to the active `Waypoint`
+#[derive(Component, Reflect, Debug, Clone, Default)]
+#[reflect(Component)]
+pub struct WaypointQueue(pub VecDeque<Vec3>);
+
+const WAYPOINT_ARRIVAL_TOLERANCE: f32 = 0.3;
+
+/// pop the next target out of `WaypointQueue` into `Waypoint` once the current one is reached
+pub fn advance_waypoint_queue(mut query: Query<(&Transform, &mut WaypointQueue, &mut Waypoint)>) {
+    for (transform, mut queue, mut waypoint) in &mut query {
+        if transform.translation.distance(waypoint.0) <= WAYPOINT_ARRIVAL_TOLERANCE {
+            if let Some(next) = queue.0.pop_front() {
+                waypoint.0 = next;
+            }
+        }
+    }
+}
 
 // control the quad with keyboard inputs
 pub fn control_quadcopter(
+    mut commands: Commands,
     keyboard_inputs: Res<ButtonInput<KeyCode>>,
-    mut quadcopter_query: Single<&mut QuadcopterControls, With<Multicopter>>,
+    mut quadcopter_query: Single<(&mut QuadcopterControls, &FrameConfig)>,
     mut time: ResMut<Time<Virtual>>,
     mut quadcopter_transform_query: Single<
         (
+            Entity,
             &mut Transform,
             &mut LinearVelocity,
             &mut AngularVelocity,
             &ComputedMass,
+            &SimulatedImu,
+            Option<&Waypoint>,
         ),
         With<Multicopter>,
     >,
     gravity: Res<Gravity>,
     mut desired_altitude: Local<Option<f32>>,
     mut integral_term: Local<f32>,
+    mut velocity_integral: Local<Vec3>,
+    mut prev_velocity_error: Local<Vec3>,
+    mut desired_yaw_heading: Local<Option<f32>>,
 ) {
     // TODO: unpause on assets loaded instead of manually doing it
     if keyboard_inputs.just_pressed(KeyCode::KeyP) {
@@ -152,18 +194,46 @@ pub fn control_quadcopter(
             time.pause();
         }
     }
+    // toggle an automatic patrol around a square of waypoints, to fly the position/velocity
+    // cascade instead of the manual stick controls
+    if keyboard_inputs.just_pressed(KeyCode::KeyT) {
+        let quadcopter_entity = quadcopter_transform_query.0;
+        if quadcopter_transform_query.6.is_some() {
+            println!("Disabling waypoint patrol");
+            commands
+                .entity(quadcopter_entity)
+                .remove::<(Waypoint, WaypointQueue)>();
+        } else {
+            println!("Enabling waypoint patrol");
+            let home = quadcopter_transform_query.1.translation;
+            commands.entity(quadcopter_entity).insert((
+                Waypoint(home + Vec3::new(3., 0., 0.)),
+                WaypointQueue(VecDeque::from([
+                    home + Vec3::new(3., 0., 3.),
+                    home + Vec3::new(0., 0., 3.),
+                    home,
+                ])),
+            ));
+            // start the velocity cascade clean instead of carrying over integral windup from
+            // whatever ran (manual or a previous patrol) before this one
+            *velocity_integral = Vec3::ZERO;
+            *prev_velocity_error = Vec3::ZERO;
+        }
+    }
     // reset copter position
     if keyboard_inputs.just_pressed(KeyCode::KeyR) {
         println!("Resetting quadcopter");
-        *quadcopter_transform_query.0 = Transform::from_xyz(0., 2., 0.);
-        *quadcopter_transform_query.1 = LinearVelocity(Vec3::ZERO);
-        *quadcopter_transform_query.2 = AngularVelocity(Vec3::ZERO);
+        *quadcopter_transform_query.1 = Transform::from_xyz(0., 2., 0.);
+        *quadcopter_transform_query.2 = LinearVelocity(Vec3::ZERO);
+        *quadcopter_transform_query.3 = AngularVelocity(Vec3::ZERO);
+        *velocity_integral = Vec3::ZERO;
+        *prev_velocity_error = Vec3::ZERO;
     }
-    let (transform, linear_velocity, angular_velocity, mass) =
+    let (_entity, transform, linear_velocity, _angular_velocity, mass, imu, waypoint) =
         quadcopter_transform_query.into_inner();
 
-    // transform angular velocity to body frame
-    let angular_velocity = transform.rotation.inverse() * angular_velocity.0;
+    // consume the IMU's estimated body angular velocity rather than ground truth
+    let angular_velocity = imu.gyro_reading;
 
     // on first run, set the desired altitude to our current altitude
     if desired_altitude.is_none() {
@@ -174,8 +244,8 @@ pub fn control_quadcopter(
     let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
     let mut desired_pitch = 0.;
     let mut desired_roll = 0.;
-    // we control yaw rate instead of yaw, so we always set the desired yaw to the current
-    let desired_yaw = yaw;
+    // on first run, start the desired heading at our current yaw
+    let desired_yaw_heading = desired_yaw_heading.get_or_insert(yaw);
     let mut desired_yaw_rate = 0.;
 
     if keyboard_inputs.pressed(KeyCode::KeyW) {
@@ -207,6 +277,24 @@ pub fn control_quadcopter(
         desired_yaw_rate -= 1.;
     }
 
+    // integrate the commanded yaw rate into a persistent heading setpoint rather than tracking
+    // the current yaw every frame. while a rate is actively being commanded, apply it at full
+    // strength, or sustained Q/E-hold would get throttled as soon as the (fast) attitude
+    // controller catches up to within the threshold. `fade_rate` is applied only once Q/E are
+    // released, when the commanded rate is already zero, so it leaves that zero unchanged and the
+    // setpoint holds its last value
+    let yaw_fade_threshold = 5.0_f32.to_radians();
+    let yaw_rate_commanded =
+        keyboard_inputs.pressed(KeyCode::KeyQ) || keyboard_inputs.pressed(KeyCode::KeyE);
+    let remaining_heading_error = angle_diff(*desired_yaw_heading, yaw);
+    let yaw_rate_to_integrate = if yaw_rate_commanded {
+        desired_yaw_rate
+    } else {
+        fade_rate(desired_yaw_rate, remaining_heading_error, yaw_fade_threshold)
+    };
+    *desired_yaw_heading += dt * yaw_rate_to_integrate;
+    let desired_yaw = *desired_yaw_heading;
+
     // target hover
     let vertical_proportional_gain = 40.;
     let vertical_derivative_gain = 30.;
@@ -218,7 +306,43 @@ pub fn control_quadcopter(
     let d_term = vertical_derivative_gain * (0. - vertical_velocity);
     *integral_term += dt * (*desired_altitude - altitude);
     let i_term = vertical_i_gain * *integral_term;
-    let desired_vertical_thrust = p_term + i_term + d_term - gravity_force;
+    let mut desired_vertical_thrust = p_term + i_term + d_term - gravity_force;
+
+    // if we have a waypoint, a position/velocity cascade overrides the manual stick + altitude
+    // hold above: position error -> desired velocity -> velocity error -> desired acceleration,
+    // which (after gravity compensation) becomes the desired thrust vector for this step
+    if let Some(Waypoint(target)) = waypoint {
+        let position_p_gain = 1.5;
+        let max_speed = 3.0;
+        let position_error = *target - transform.translation;
+        let desired_velocity = (position_p_gain * position_error).clamp_length_max(max_speed);
+
+        let velocity_p_gain = 2.0;
+        let velocity_i_gain = 0.3;
+        let velocity_d_gain = 0.1;
+        let velocity_error = desired_velocity - linear_velocity.0;
+        *velocity_integral += dt * velocity_error;
+        let velocity_derivative = (velocity_error - *prev_velocity_error) / dt.max(1e-6);
+        *prev_velocity_error = velocity_error;
+
+        let desired_acceleration = velocity_p_gain * velocity_error
+            + velocity_i_gain * *velocity_integral
+            + velocity_d_gain * velocity_derivative;
+
+        // gravity compensation: the thrust vector must also cancel gravity
+        let desired_thrust_vector = desired_acceleration + Vec3::Y * gravity.0.y.abs();
+        let thrust_magnitude = desired_thrust_vector.length().max(1e-6);
+        desired_vertical_thrust = mass.value() * thrust_magnitude;
+
+        // project the world-frame thrust direction into the yaw-rotated body frame to get the
+        // tilt angles that would point the thrust vector the way we want. negated to match the
+        // manual controls' convention (e.g. KeyD sets desired_roll negative for +X motion)
+        let (sin_yaw, cos_yaw) = yaw.sin_cos();
+        let body_right = desired_thrust_vector.x * cos_yaw - desired_thrust_vector.z * sin_yaw;
+        let body_forward = -desired_thrust_vector.x * sin_yaw - desired_thrust_vector.z * cos_yaw;
+        desired_roll = -(body_right / thrust_magnitude).clamp(-1., 1.).asin();
+        desired_pitch = -(body_forward / thrust_magnitude).clamp(-1., 1.).asin();
+    }
 
     // the proportion of thrust that actually helps go up
     let vertical_thrust_coeff = transform.local_y().dot(Vec3::Y);
@@ -229,16 +353,13 @@ pub fn control_quadcopter(
         desired_vertical_thrust / vertical_thrust_coeff
     };
 
-    // the portion of the whole that each propeller must take
-    // they will sum to 1. and thus achieve the necessary thrust, but may take on different values
-    // in order to achieve the necessary angle control
-    let mut propeller_thrust_proportions = [0.0_f32; 4];
-
     let angular_proportional_gain = 0.05;
     let angular_derivative_gain = 0.01;
-    let roll_diff = desired_roll - roll;
-    let pitch_diff = desired_pitch - pitch;
-    let yaw_diff = desired_yaw - yaw;
+    // wrap each error into [-pi, pi] so the controller always takes the shortest arc, instead of
+    // spinning the long way around near the +-pi wraparound
+    let roll_diff = angle_diff(desired_roll, roll);
+    let pitch_diff = angle_diff(desired_pitch, pitch);
+    let yaw_diff = angle_diff(desired_yaw, yaw);
     let angular_velocity_difference = Vec3 {
         x: 0.,
         y: desired_yaw_rate,
@@ -257,66 +378,56 @@ pub fn control_quadcopter(
     let d_roll = angular_derivative_gain * angular_velocity_difference.z;
     let desired_roll_torque = p_roll + d_roll;
 
-    // map the desired roll pitch yaw torques to the propellers according to their positions
-    propeller_thrust_proportions[0] =
-        -desired_pitch_torque + desired_roll_torque + desired_yaw_torque;
-    propeller_thrust_proportions[1] =
-        -desired_pitch_torque - desired_roll_torque - desired_yaw_torque;
-    propeller_thrust_proportions[2] =
-        desired_pitch_torque + desired_roll_torque - desired_yaw_torque;
-    propeller_thrust_proportions[3] =
-        desired_pitch_torque - desired_roll_torque + desired_yaw_torque;
+    // map the desired roll/pitch/yaw torques to each motor according to the frame's layout
+    let (controls, frame) = quadcopter_query.into_inner();
+    let motor_count = frame.motors.len();
+    // baseline corresponding to equal control authority for every motor
+    let baseline = 1. / motor_count as f32;
+    let propeller_thrust_proportions =
+        frame.mix(desired_roll_torque, desired_pitch_torque, desired_yaw_torque);
 
     // compute the values such that no propeller wants to go backwards (may result in less control authority)
-    let reduction_factor =
-        propeller_thrust_proportions
-            .iter()
-            .cloned()
-            .fold(1.0_f32, |acc, proportion| {
-                acc.min(if proportion < -0.25 {
-                    -0.25 / proportion
-                } else {
-                    1.
-                })
-            });
-
-    // shrink commands down if necessary and add the 0.25 baseline corresponding to equal control of each
-    let propeller_thrust_proportions =
-        propeller_thrust_proportions.map(|proportion| 0.25 + proportion * reduction_factor);
+    let reduction_factor = propeller_thrust_proportions
+        .iter()
+        .cloned()
+        .fold(1.0_f32, |acc, proportion| {
+            acc.min(if proportion < -baseline {
+                -baseline / proportion
+            } else {
+                1.
+            })
+        });
+
+    // shrink commands down if necessary and add the baseline corresponding to equal control of each
+    let propeller_thrust_proportions = propeller_thrust_proportions
+        .into_iter()
+        .map(|proportion| baseline + proportion * reduction_factor);
 
-    let controls = quadcopter_query.as_mut();
     let necessary_propeller_rotation_rate = (needed_vertical_thrust / THRUST_CONSTANT).sqrt();
     controls.0 = propeller_thrust_proportions
-        .map(|proportion| necessary_propeller_rotation_rate * proportion);
+        .map(|proportion| necessary_propeller_rotation_rate * proportion)
+        .collect();
 }
 
-pub fn quadcopter_dynamics(
-    quadcopter_query: Query<(
-        &Multicopter,
-        &GlobalTransform,
-        &QuadcopterControls,
-        &AngularInertia,
-        Forces,
-    )>,
+/// sample each quadcopter's [`SimulatedImu`] from ground-truth state
+pub fn update_simulated_imus(
+    mut imu_query: Query<(&mut SimulatedImu, &Transform, &LinearVelocity, &AngularVelocity)>,
+    gravity: Res<Gravity>,
     time: Res<Time<Virtual>>,
 ) {
     let dt = time.delta_secs();
-    for (multicopter, transform, controls, inertia, mut forces) in quadcopter_query {
-        let Ok(force_torque) = multicopter
-            .force_torque(
-                transform,
-                &forces.angular_velocity(),
-                &controls.0.iter().cloned().collect(),
-                &inertia.tensor().to_mat3(),
-            )
-            .map_err(|err| {
-                error!(err);
-            })
-        else {
-            continue;
-        };
-        forces.apply_linear_impulse(dt * force_torque.force);
-        forces.apply_angular_impulse(dt * force_torque.torque);
+    if dt <= 0. {
+        return;
+    }
+    for (mut imu, transform, linear_velocity, angular_velocity) in &mut imu_query {
+        let true_angular_velocity_body = transform.rotation.inverse() * angular_velocity.0;
+        imu.sample(
+            dt,
+            transform.rotation,
+            true_angular_velocity_body,
+            linear_velocity.0,
+            gravity.0,
+        );
     }
 }
 
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// per-airframe aerodynamic coefficients used by [`crate::Multicopter::force_torque`]
+///
+/// defaults to all-zero, i.e. no aerodynamic effects, so existing callers of
+/// [`crate::Multicopter::new`] see no behavior change until they opt in with
+/// [`crate::Multicopter::with_aero`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Reflect, Default)]
+pub struct AeroConfig {
+    /// air density, kg/m^3
+    pub rho: f32,
+    /// drag coefficient
+    pub drag_coefficient: f32,
+    /// reference area presented to the relative wind, m^2
+    pub reference_area: f32,
+    /// rotor induced/blade-flapping drag coefficient: scales a drag force opposing horizontal
+    /// airspeed by the total thrust magnitude
+    pub flap_coefficient: f32,
+}
+
+impl AeroConfig {
+    pub fn new(rho: f32, drag_coefficient: f32, reference_area: f32, flap_coefficient: f32) -> Self {
+        Self {
+            rho,
+            drag_coefficient,
+            reference_area,
+            flap_coefficient,
+        }
+    }
+}
+
+/// a time-varying world wind field: a constant component plus a sinusoidal gust
+#[derive(Debug, Clone, Copy, Resource, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct Wind {
+    /// steady wind velocity, world frame, m/s
+    pub constant: Vec3,
+    /// gust amplitude, world frame, m/s
+    pub gust_amplitude: Vec3,
+    /// gust angular frequency, rad/s
+    pub gust_frequency: f32,
+}
+
+impl Wind {
+    pub fn new(constant: Vec3, gust_amplitude: Vec3, gust_frequency: f32) -> Self {
+        Self {
+            constant,
+            gust_amplitude,
+            gust_frequency,
+        }
+    }
+
+    /// the wind velocity at `elapsed_secs` into the simulation, world frame
+    pub fn at(&self, elapsed_secs: f32) -> Vec3 {
+        self.constant + self.gust_amplitude * (self.gust_frequency * elapsed_secs).sin()
+    }
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self::new(Vec3::ZERO, Vec3::ZERO, 0.)
+    }
+}
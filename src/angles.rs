@@ -0,0 +1,69 @@
+/// the shortest-path angular difference `desired - actual`, wrapped into `[-pi, pi]`
+///
+/// naive subtraction jumps by +-2*pi near the wraparound, which makes a PD controller spin the
+/// long way around; this guarantees the result is always the shortest arc between the two angles.
+pub fn angle_diff(desired: f32, actual: f32) -> f32 {
+    let raw = desired - actual;
+    raw.sin().atan2(raw.cos())
+}
+
+/// smoothly fade a commanded rate toward zero as `remaining_error` shrinks below
+/// `fade_threshold`, so a rate command integrated into a setpoint converges onto its target
+/// exactly instead of snapping through a wraparound discontinuity once it arrives
+///
+/// `fade_threshold <= 0.` disables fading and returns `rate_cmd` unchanged.
+pub fn fade_rate(rate_cmd: f32, remaining_error: f32, fade_threshold: f32) -> f32 {
+    if fade_threshold <= 0. {
+        return rate_cmd;
+    }
+    let fade = (remaining_error.abs() / fade_threshold).clamp(0., 1.);
+    rate_cmd * fade
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn angle_diff_takes_the_shortest_arc() {
+        assert!((angle_diff(0.1, -0.1) - 0.2).abs() < 1e-6);
+        assert!((angle_diff(-0.1, 0.1) + 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn angle_diff_wraps_across_the_pi_boundary() {
+        // desired just short of +pi and actual just short of -pi are only a hair apart the short
+        // way around (backwards through the wraparound), not almost a full turn apart
+        let diff = angle_diff(PI - 0.1, -PI + 0.1);
+        assert!((diff + 0.2).abs() < 1e-5, "diff was {diff}");
+    }
+
+    #[test]
+    fn angle_diff_of_equal_angles_is_zero() {
+        assert!(angle_diff(1.23, 1.23).abs() < 1e-6);
+        assert!((angle_diff(PI, -PI)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn fade_rate_passes_through_unchanged_when_disabled() {
+        assert_eq!(fade_rate(2., 0.01, 0.), 2.);
+        assert_eq!(fade_rate(2., 0.01, -1.), 2.);
+    }
+
+    #[test]
+    fn fade_rate_is_unfaded_when_error_exceeds_threshold() {
+        assert_eq!(fade_rate(2., 1., 0.1), 2.);
+    }
+
+    #[test]
+    fn fade_rate_scales_linearly_within_the_threshold() {
+        let faded = fade_rate(2., 0.05, 0.1);
+        assert!((faded - 1.).abs() < 1e-6, "faded was {faded}");
+    }
+
+    #[test]
+    fn fade_rate_is_zero_at_zero_remaining_error() {
+        assert_eq!(fade_rate(2., 0., 0.1), 0.);
+    }
+}
@@ -0,0 +1,61 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{BatteryModel, Multicopter, MotorModel, QuadcopterControls, Wind};
+
+/// slew each quadcopter's [`MotorModel`] toward the commanded omega (sagged by [`BatteryModel`]
+/// if one is present), then apply the resulting [`Multicopter::force_torque`] as impulses
+///
+/// the sole `FixedUpdate` system driving quadcopter physics - shared by the interactive example
+/// and [`crate::MulticopterEnv`] so the two can't drift apart into different dynamics.
+pub fn quadcopter_dynamics(
+    quadcopter_query: Query<(
+        &Multicopter,
+        &GlobalTransform,
+        &LinearVelocity,
+        &QuadcopterControls,
+        &mut MotorModel,
+        &AngularInertia,
+        Forces,
+    )>,
+    battery: Option<Res<BatteryModel>>,
+    wind: Res<Wind>,
+    time: Res<Time<Virtual>>,
+) {
+    let dt = time.delta_secs();
+    let wind_velocity = wind.at(time.elapsed_secs());
+    for (multicopter, transform, linear_velocity, controls, mut motor_model, inertia, mut forces) in
+        quadcopter_query
+    {
+        // current draw scales with thrust demand; approximate it with summed omega^2
+        let omega_max = match &battery {
+            Some(battery) => {
+                let current_draw_proxy: f32 = motor_model.omega.iter().map(|o| o.powi(2)).sum();
+                motor_model.omega_max * battery.voltage_scale(current_draw_proxy)
+            }
+            None => motor_model.omega_max,
+        };
+        if let Err(err) = motor_model.step(dt, &controls.0, omega_max) {
+            error!(err);
+            continue;
+        }
+
+        let Ok(force_torque) = multicopter
+            .force_torque(
+                transform,
+                &forces.angular_velocity(),
+                &linear_velocity.0,
+                &motor_model.omega,
+                &inertia.tensor().to_mat3(),
+                wind_velocity,
+            )
+            .map_err(|err| {
+                error!(err);
+            })
+        else {
+            continue;
+        };
+        forces.apply_linear_impulse(dt * force_torque.force);
+        forces.apply_angular_impulse(dt * force_torque.torque);
+    }
+}
@@ -0,0 +1,172 @@
+//! a deterministic, render-free stepping API for driving the simulator like a Gym/RL environment
+//!
+//! gated behind the `rl` feature so consumers that only want the interactive simulation don't
+//! pull in a second headless [`App`] and its physics plugins.
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{quadcopter_dynamics, QuadcopterControls, Wind};
+
+/// flattened sensor state: body-frame orientation, body-frame angular velocity, world position,
+/// and world linear velocity
+#[derive(Debug, Clone, Copy)]
+pub struct Observation {
+    pub rotation: Quat,
+    pub angular_velocity: Vec3,
+    pub position: Vec3,
+    pub linear_velocity: Vec3,
+}
+
+impl Observation {
+    /// flatten to `[quat.xyzw, ang_vel.xyz, pos.xyz, lin_vel.xyz]` for feeding to a policy
+    pub fn to_vec(self) -> Vec<f32> {
+        let mut out = Vec::with_capacity(13);
+        out.extend_from_slice(&self.rotation.to_array());
+        out.extend_from_slice(&self.angular_velocity.to_array());
+        out.extend_from_slice(&self.position.to_array());
+        out.extend_from_slice(&self.linear_velocity.to_array());
+        out
+    }
+}
+
+/// scores an observation and decides whether the episode is over; returns `(reward, terminated)`
+pub type RewardFn = Box<dyn FnMut(&Observation) -> (f32, bool) + Send + Sync>;
+
+/// (re)spawns the quadcopter (and anything else the task needs) into `world`, returning its
+/// `Entity`; called once per [`MulticopterEnv::reset`] with whatever seed was passed to it, so a
+/// task can randomize the initial pose/target reproducibly
+pub type SpawnFn = Box<dyn Fn(&mut World, Option<u64>) -> Entity + Send + Sync>;
+
+/// a headless, render-free wrapper around the simulator for reinforcement-learning use
+///
+/// runs its own [`App`] built from [`MinimalPlugins`] plus physics only - no window, renderer,
+/// egui, or inspector - so `step` can be called as fast as a training loop wants instead of
+/// being paced by wall-clock time.
+pub struct MulticopterEnv {
+    app: App,
+    dt: f32,
+    max_steps: u32,
+    elapsed_steps: u32,
+    entity: Option<Entity>,
+    spawn: SpawnFn,
+    reward_fn: RewardFn,
+}
+
+impl MulticopterEnv {
+    /// `dt` is the fixed timestep each `step` advances by. `spawn` builds the quadcopter (and any
+    /// scenery/targets the task needs), and must include a [`crate::MotorModel`] - dynamics are
+    /// driven by [`crate::quadcopter_dynamics`], the same system the interactive example uses, so
+    /// motor lag and (if a [`crate::BatteryModel`] resource is present) voltage sag apply here
+    /// too. `reward_fn` scores each resulting observation.
+    pub fn new(dt: f32, max_steps: u32, spawn: SpawnFn, reward_fn: RewardFn) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PhysicsPlugins::default()))
+            .insert_resource(Time::<Fixed>::from_seconds(dt as f64))
+            .insert_resource(Wind::default())
+            .add_systems(FixedUpdate, quadcopter_dynamics);
+        // `step` is the sole driver of simulated time via `advance_by` below; pausing keeps the
+        // built-in time system from also folding in however long the caller's policy takes to
+        // compute each action, which would make `FixedUpdate` run a nondeterministic number of
+        // ticks per `step` call
+        app.world_mut().resource_mut::<Time<Virtual>>().pause();
+
+        Self {
+            app,
+            dt,
+            max_steps,
+            elapsed_steps: 0,
+            entity: None,
+            spawn,
+            reward_fn,
+        }
+    }
+
+    /// set the world wind field, e.g. to train or evaluate disturbance rejection
+    pub fn with_wind(mut self, wind: Wind) -> Self {
+        self.app.insert_resource(wind);
+        self
+    }
+
+    fn observe(&self) -> Observation {
+        let entity = self
+            .entity
+            .expect("MulticopterEnv::reset must be called before observing");
+        let world = self.app.world();
+        let transform = world
+            .get::<GlobalTransform>(entity)
+            .expect("spawned quadcopter is missing a GlobalTransform");
+        let linear_velocity = world
+            .get::<LinearVelocity>(entity)
+            .expect("spawned quadcopter is missing a LinearVelocity");
+        let angular_velocity = world
+            .get::<AngularVelocity>(entity)
+            .expect("spawned quadcopter is missing an AngularVelocity");
+        Observation {
+            rotation: transform.rotation(),
+            angular_velocity: transform.rotation().inverse() * angular_velocity.0,
+            position: transform.translation(),
+            linear_velocity: linear_velocity.0,
+        }
+    }
+
+    /// respawn the quadcopter (optionally seeded, so `spawn` can randomize the initial pose),
+    /// zero its velocities, and return the initial observation
+    pub fn reset(&mut self, seed: Option<u64>) -> Observation {
+        self.elapsed_steps = 0;
+        if let Some(entity) = self.entity.take() {
+            self.app.world_mut().despawn(entity);
+        }
+
+        let entity = (self.spawn)(self.app.world_mut(), seed);
+        let mut world = self.app.world_mut();
+        if let Some(mut linear_velocity) = world.get_mut::<LinearVelocity>(entity) {
+            *linear_velocity = LinearVelocity::ZERO;
+        }
+        if let Some(mut angular_velocity) = world.get_mut::<AngularVelocity>(entity) {
+            *angular_velocity = AngularVelocity::ZERO;
+        }
+
+        self.entity = Some(entity);
+        self.observe()
+    }
+
+    /// write `action` into the quadcopter's [`QuadcopterControls`], advance exactly one
+    /// `FixedUpdate` tick, and return `(observation, reward, terminated, truncated)`
+    pub fn step(&mut self, action: Vec<f32>) -> (Observation, f32, bool, bool) {
+        let entity = self
+            .entity
+            .expect("MulticopterEnv::reset must be called before step");
+        if let Some(mut controls) = self.app.world_mut().get_mut::<QuadcopterControls>(entity) {
+            controls.0 = action;
+        }
+
+        self.app
+            .world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .advance_by(Duration::from_secs_f32(self.dt));
+        self.app.update();
+        self.elapsed_steps += 1;
+
+        let observation = self.observe();
+        let (reward, terminated) = (self.reward_fn)(&observation);
+        let truncated = !terminated && self.elapsed_steps >= self.max_steps;
+        (observation, reward, terminated, truncated)
+    }
+}
+
+/// a ready-made reward/termination closure for a hover task: rewards staying close to `target`
+/// with low tilt, and terminates on ground contact or straying more than 50 m from it
+pub fn hover_reward(target: Vec3) -> RewardFn {
+    Box::new(move |observation: &Observation| {
+        let distance = observation.position.distance(target);
+        let (_, pitch, roll) = observation.rotation.to_euler(EulerRot::YXZ);
+        let tilt = pitch.abs() + roll.abs();
+        let reward = -distance - tilt;
+
+        let hit_ground = observation.position.y <= 0.05;
+        let out_of_bounds = distance > 50.;
+        (reward, hit_ground || out_of_bounds)
+    })
+}
@@ -0,0 +1,136 @@
+use crate::{PropellerInfo, RotationDirection};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// a single motor's placement and mixing behavior within a [`FrameConfig`], modeled on
+/// ArduPilot's motor tables
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
+pub struct MotorConfig {
+    /// angle around the yaw axis, in degrees, measured from the body +x axis
+    pub angle_deg: f32,
+    /// distance from the center of mass to the motor, in meters
+    pub arm_radius: f32,
+    /// +1 for counter-clockwise rotating props, -1 for clockwise
+    pub yaw_factor: f32,
+    /// the order this motor should be spun in during a thrust test
+    pub thrust_test_order: usize,
+}
+
+impl MotorConfig {
+    pub fn new(angle_deg: f32, arm_radius: f32, yaw_factor: f32, thrust_test_order: usize) -> Self {
+        Self {
+            angle_deg,
+            arm_radius,
+            yaw_factor,
+            thrust_test_order,
+        }
+    }
+
+    fn rotation_direction(&self) -> RotationDirection {
+        if self.yaw_factor >= 0. {
+            RotationDirection::CounterClockWise
+        } else {
+            RotationDirection::ClockWise
+        }
+    }
+}
+
+/// a motor layout for an arbitrary multirotor airframe
+///
+/// builds the [`PropellerInfo`] list that [`crate::Multicopter::new`] expects, and provides the
+/// generic roll/pitch/yaw mixer used to turn attitude commands into per-motor thrust factors so
+/// the same controller works regardless of motor count or layout
+#[derive(Debug, Clone, Component, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub struct FrameConfig {
+    pub motors: Vec<MotorConfig>,
+}
+
+impl FrameConfig {
+    pub fn new(motors: Vec<MotorConfig>) -> Self {
+        assert!(!motors.is_empty(), "a frame needs at least one motor");
+        Self { motors }
+    }
+
+    /// quad in a '+' layout: motors on the forward/right/aft/left axes
+    pub fn quad_plus() -> Self {
+        Self::new(vec![
+            MotorConfig::new(90., 1., 1., 0),
+            MotorConfig::new(270., 1., 1., 1),
+            MotorConfig::new(0., 1., -1., 2),
+            MotorConfig::new(180., 1., -1., 3),
+        ])
+    }
+
+    /// quad in an 'X' layout: motors on the diagonals, alternating rotation direction
+    pub fn quad_x() -> Self {
+        Self::new(vec![
+            MotorConfig::new(45., 1., 1., 0),
+            MotorConfig::new(135., 1., -1., 1),
+            MotorConfig::new(225., 1., 1., 2),
+            MotorConfig::new(315., 1., -1., 3),
+        ])
+    }
+
+    /// hexacopter, motors evenly spaced 60 degrees apart with alternating rotation direction
+    pub fn hexa() -> Self {
+        Self::new(
+            (0..6)
+                .map(|i| MotorConfig::new(60. * i as f32, 1., if i % 2 == 0 { 1. } else { -1. }, i))
+                .collect(),
+        )
+    }
+
+    /// octocopter, motors evenly spaced 45 degrees apart with alternating rotation direction
+    pub fn octa() -> Self {
+        Self::new(
+            (0..8)
+                .map(|i| MotorConfig::new(45. * i as f32, 1., if i % 2 == 0 { 1. } else { -1. }, i))
+                .collect(),
+        )
+    }
+
+    /// generate the propeller positions/directions this layout implies
+    ///
+    /// each motor's arm radius is scaled by `arm_radius`, and every propeller shares the same
+    /// thrust/drag constants and points straight up in the body frame
+    pub fn propellers(
+        &self,
+        arm_radius: f32,
+        thrust_constant: f32,
+        drag_constant: f32,
+    ) -> Vec<PropellerInfo> {
+        self.motors
+            .iter()
+            .map(|motor| {
+                let theta = motor.angle_deg.to_radians();
+                let radius = motor.arm_radius * arm_radius;
+                PropellerInfo {
+                    position: Vec3::new(radius * theta.cos(), 0., radius * theta.sin()),
+                    direction: Dir3::new(Vec3::Y).unwrap(),
+                    thrust_constant,
+                    drag_constant,
+                    rotation_direction: motor.rotation_direction(),
+                }
+            })
+            .collect()
+    }
+
+    /// mix roll/pitch/yaw commands into an unnormalized per-motor thrust contribution
+    ///
+    /// for motor `i` at angle `theta`, `proportion[i] = roll_cmd * cos(theta) + pitch_cmd *
+    /// -sin(theta) + yaw_cmd * yaw_factor[i]`; this generalizes the old 4-entry hardcoded table to
+    /// any motor count and layout. callers are expected to add a hover baseline and clamp the
+    /// result the same way regardless of how many motors are present.
+    pub fn mix(&self, roll_cmd: f32, pitch_cmd: f32, yaw_cmd: f32) -> Vec<f32> {
+        self.motors
+            .iter()
+            .map(|motor| {
+                let theta = motor.angle_deg.to_radians();
+                let roll_factor = theta.cos();
+                let pitch_factor = -theta.sin();
+                roll_cmd * roll_factor + pitch_cmd * pitch_factor + yaw_cmd * motor.yaw_factor
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+fn randn_vec3(rng: &mut impl Rng) -> Vec3 {
+    Vec3::new(
+        rng.sample(StandardNormal),
+        rng.sample(StandardNormal),
+        rng.sample(StandardNormal),
+    )
+}
+
+/// a simulated IMU reporting noisy, bias-corrupted body-frame gyro and accelerometer readings
+///
+/// call [`SimulatedImu::sample`] once per fixed step with the ground-truth body angular velocity
+/// and world-frame state; it corrupts them the way real hardware does (Gaussian white noise plus
+/// a slowly-drifting bias random walk) and stores the result so controllers can consume estimated
+/// state instead of truth.
+#[derive(Component, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub struct SimulatedImu {
+    /// standard deviation of gyro measurement noise, in rad/s
+    pub gyro_noise: f32,
+    /// standard deviation of accelerometer measurement noise, in m/s^2
+    pub accel_noise: f32,
+    /// standard deviation of the gyro bias random walk, in (rad/s) per sqrt(s)
+    pub gyro_bias_walk: f32,
+    /// standard deviation of the accelerometer bias random walk, in (m/s^2) per sqrt(s)
+    pub accel_bias_walk: f32,
+    gyro_bias: Vec3,
+    accel_bias: Vec3,
+    /// linear velocity from the previous sample, used to finite-difference true acceleration
+    prev_velocity: Vec3,
+    /// the most recent simulated gyro reading, body frame, rad/s
+    pub gyro_reading: Vec3,
+    /// the most recent simulated accelerometer (specific force) reading, body frame, m/s^2
+    pub accel_reading: Vec3,
+}
+
+impl SimulatedImu {
+    pub fn new(gyro_noise: f32, accel_noise: f32, gyro_bias_walk: f32, accel_bias_walk: f32) -> Self {
+        Self {
+            gyro_noise,
+            accel_noise,
+            gyro_bias_walk,
+            accel_bias_walk,
+            gyro_bias: Vec3::ZERO,
+            accel_bias: Vec3::ZERO,
+            prev_velocity: Vec3::ZERO,
+            gyro_reading: Vec3::ZERO,
+            accel_reading: Vec3::ZERO,
+        }
+    }
+
+    /// sample a new gyro/accelerometer reading and store it on the component
+    ///
+    /// `rotation` and `linear_velocity_world` are ground truth in the world frame,
+    /// `true_angular_velocity_body` is ground-truth body angular velocity, and `gravity_world` is
+    /// the world gravity vector. returns `(gyro_reading, accel_reading)`.
+    pub fn sample(
+        &mut self,
+        dt: f32,
+        rotation: Quat,
+        true_angular_velocity_body: Vec3,
+        linear_velocity_world: Vec3,
+        gravity_world: Vec3,
+    ) -> (Vec3, Vec3) {
+        // specific force is true acceleration minus gravity, rotated into the body frame
+        let accel_world = (linear_velocity_world - self.prev_velocity) / dt.max(1e-6);
+        self.prev_velocity = linear_velocity_world;
+        let specific_force_body = rotation.inverse() * (accel_world - gravity_world);
+
+        let mut rng = rand::thread_rng();
+        self.gyro_bias += dt.sqrt() * self.gyro_bias_walk * randn_vec3(&mut rng);
+        self.accel_bias += dt.sqrt() * self.accel_bias_walk * randn_vec3(&mut rng);
+
+        self.gyro_reading =
+            true_angular_velocity_body + self.gyro_bias + self.gyro_noise * randn_vec3(&mut rng);
+        self.accel_reading =
+            specific_force_body + self.accel_bias + self.accel_noise * randn_vec3(&mut rng);
+
+        (self.gyro_reading, self.accel_reading)
+    }
+}
+
+impl Default for SimulatedImu {
+    // roughly a cheap consumer-grade MEMS IMU
+    fn default() -> Self {
+        Self::new(0.01, 0.05, 0.001, 0.001)
+    }
+}
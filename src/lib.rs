@@ -1,12 +1,41 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+mod frame;
+pub use frame::{FrameConfig, MotorConfig};
+
+mod aero;
+pub use aero::{AeroConfig, Wind};
+
+mod angles;
+pub use angles::{angle_diff, fade_rate};
+
+mod imu;
+pub use imu::SimulatedImu;
+
+mod motor;
+pub use motor::{BatteryModel, MotorModel};
+
+mod dynamics;
+pub use dynamics::quadcopter_dynamics;
+
+#[cfg(feature = "rl")]
+mod env;
+#[cfg(feature = "rl")]
+pub use env::{hover_reward, MulticopterEnv, Observation, RewardFn, SpawnFn};
+
 #[derive(Debug)]
 pub struct QuadcopterForceTorque {
     pub force: Vec3,
     pub torque: Vec3,
 }
 
+/// raw per-motor thrust commands (omega) for a [`Multicopter`], one entry per propeller in the
+/// order given to [`Multicopter::new`]
+#[derive(Component, Reflect, Debug, Clone, Default)]
+#[reflect(Component)]
+pub struct QuadcopterControls(pub Vec<f32>);
+
 #[derive(Serialize, Deserialize, Reflect)]
 pub enum RotationDirection {
     CounterClockWise,
@@ -27,6 +56,7 @@ pub struct PropellerInfo {
 #[reflect(Component)]
 pub struct Multicopter {
     propellers: Vec<PropellerInfo>,
+    aero: AeroConfig,
 }
 
 impl Multicopter {
@@ -36,16 +66,16 @@ impl Multicopter {
         &self,
         quadcopter_state: &GlobalTransform,
         angular_velocity: &Vec3,
-        // TODO: instead of inputting omega directly, allow for thrust curves or something
+        linear_velocity: &Vec3,
+        // the *actual* per-motor omega, e.g. as slewed by a `MotorModel`, not the raw command
         quadcopter_control_inputs: &Vec<f32>,
         inertia: &Mat3,
+        wind: Vec3,
     ) -> Result<QuadcopterForceTorque, String> {
         if quadcopter_control_inputs.len() != self.propellers.len() {
             return Err("Incorrect control input length".into());
         }
 
-        // TODO: add aerodynamic effects
-
         // the force of each prop
         let forces: Vec<_> = self
             .propellers
@@ -76,7 +106,20 @@ impl Multicopter {
             })
             .sum();
 
-        let force = quadcopter_state.rotation() * thrust;
+        // aerodynamic effects: quadratic body drag against the relative wind, plus rotor
+        // induced/blade-flapping drag opposing horizontal airspeed in proportion to thrust
+        let relative_velocity = *linear_velocity - wind;
+        let drag_force = -0.5
+            * self.aero.rho
+            * self.aero.drag_coefficient
+            * self.aero.reference_area
+            * relative_velocity.length()
+            * relative_velocity;
+        let horizontal_relative_velocity = Vec3::new(relative_velocity.x, 0., relative_velocity.z);
+        let flap_drag_force =
+            -self.aero.flap_coefficient * thrust.length() * horizontal_relative_velocity;
+
+        let force = quadcopter_state.rotation() * thrust + drag_force + flap_drag_force;
         let torque = quadcopter_state.rotation() * propeller_torque
             - angular_velocity.cross(inertia * angular_velocity);
         Ok(QuadcopterForceTorque { force, torque })
@@ -84,7 +127,16 @@ impl Multicopter {
 
     pub fn new(propellers: Vec<PropellerInfo>) -> Result<Self, String> {
         assert!(!propellers.is_empty(), "Don't try to simulate a 0-copter");
-        Ok(Self { propellers })
+        Ok(Self {
+            propellers,
+            aero: AeroConfig::default(),
+        })
+    }
+
+    /// configure this airframe's aerodynamic drag coefficients
+    pub fn with_aero(mut self, aero: AeroConfig) -> Self {
+        self.aero = aero;
+        self
     }
 }
 
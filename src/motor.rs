@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// first-order motor dynamics: actual omega slews toward the commanded value instead of
+/// responding instantly, capturing the spin-up lag and saturation that dominate real
+/// multicopter attitude response
+#[derive(Component, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub struct MotorModel {
+    /// time constant of the first-order lag, in seconds
+    pub tau: f32,
+    /// maximum commandable angular rate, in rad/s, before any battery sag is applied
+    pub omega_max: f32,
+    /// the actual omega of each motor, slewing toward the commanded value
+    pub omega: Vec<f32>,
+}
+
+impl MotorModel {
+    pub fn new(tau: f32, omega_max: f32, motor_count: usize) -> Self {
+        Self {
+            tau,
+            omega_max,
+            omega: vec![0.; motor_count],
+        }
+    }
+
+    /// slew `self.omega` toward `omega_cmd` with a first-order lag, clamped to `[0, omega_max]`
+    ///
+    /// `omega_max` is taken as a parameter (rather than always using `self.omega_max`) so
+    /// callers can scale it down for e.g. battery sag without mutating the configured maximum
+    pub fn step(&mut self, dt: f32, omega_cmd: &[f32], omega_max: f32) -> Result<(), String> {
+        if omega_cmd.len() != self.omega.len() {
+            return Err("Incorrect control input length".into());
+        }
+        for (omega, cmd) in self.omega.iter_mut().zip(omega_cmd) {
+            *omega += (dt / self.tau) * (cmd - *omega);
+            *omega = omega.clamp(0., omega_max);
+        }
+        Ok(())
+    }
+}
+
+/// a simple battery model whose output voltage sags under current draw
+///
+/// real current draw isn't tracked by this crate, so callers pass a proxy for it (e.g. the sum
+/// of squared actual motor omegas, which scales with total thrust demand).
+#[derive(Resource, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct BatteryModel {
+    /// no-load voltage
+    pub nominal_voltage: f32,
+    /// how much voltage sags per unit of the current-draw proxy
+    pub sag_coefficient: f32,
+}
+
+impl BatteryModel {
+    pub fn new(nominal_voltage: f32, sag_coefficient: f32) -> Self {
+        Self {
+            nominal_voltage,
+            sag_coefficient,
+        }
+    }
+
+    /// approximate scale factor in `[0, 1]` to apply to `MotorModel::omega_max`, given a proxy
+    /// for total current draw
+    pub fn voltage_scale(&self, current_draw_proxy: f32) -> f32 {
+        let voltage = (self.nominal_voltage - self.sag_coefficient * current_draw_proxy).max(0.);
+        (voltage / self.nominal_voltage).clamp(0., 1.)
+    }
+}